@@ -8,14 +8,14 @@
 //! [Dumper]: crate::dumper::Dumper
 //! [Parser]: crate::parser::Parser
 
-use std::io::SeekFrom;
+use std::io::{self, SeekFrom};
 use std::mem;
 
 use crate::data::{Mark, Type, Value};
 use crate::dumper::Dumper;
-use crate::error::Result;
+use crate::error::{Error, Result};
 use crate::object::{ObjectDump, ObjectParse};
-use crate::parser::Parser;
+use crate::parser::{Parser, DEFAULT_MAX_ALLOC};
 
 use async_recursion::async_recursion;
 use byteorder::{BigEndian, ReadBytesExt};
@@ -334,27 +334,55 @@ where
 /// assert_eq!(val, 15);
 /// # Ok::<(), Box<dyn std::error::Error>>(()) }).unwrap();
 /// ```
+///
+/// Each value is read into memory before it is parsed, so the parser refuses
+/// to read a value larger than [`max_alloc()`](AsyncParser::max_alloc) bytes.
+/// This is [DEFAULT_MAX_ALLOC] unless changed with
+/// [`with_max_alloc()`](AsyncParser::with_max_alloc).
 #[derive(Debug)]
-pub struct AsyncParser<R>(R);
+pub struct AsyncParser<R> {
+    reader: R,
+    max_alloc: usize,
+}
 
 impl<R> From<R> for AsyncParser<R>
 where
     R: AsyncReadExt + Unpin + Send,
 {
     fn from(reader: R) -> Self {
-        Self(reader)
+        Self {
+            reader,
+            max_alloc: DEFAULT_MAX_ALLOC,
+        }
+    }
+}
+
+impl<R> AsyncParser<R> {
+    /// Set the largest allocation the parser may make for a single value
+    ///
+    /// see [Parser::with_max_alloc()](crate::parser::Parser::with_max_alloc)
+    #[inline]
+    pub fn with_max_alloc(mut self, max_alloc: usize) -> Self {
+        self.max_alloc = max_alloc;
+        self
+    }
+
+    /// Get the largest allocation the parser may make for a single value
+    #[inline]
+    pub fn max_alloc(&self) -> usize {
+        self.max_alloc
     }
 }
 
 impl<R> AsRef<R> for AsyncParser<R> {
     fn as_ref(&self) -> &R {
-        &self.0
+        &self.reader
     }
 }
 
 impl<R> AsMut<R> for AsyncParser<R> {
     fn as_mut(&mut self) -> &mut R {
-        &mut self.0
+        &mut self.reader
     }
 }
 
@@ -365,19 +393,19 @@ where
     /// Turn the parser into the underlying reader
     #[inline]
     pub fn reader(self) -> R {
-        self.0
+        self.reader
     }
 
     /// Get the underlying reader as a reference
     #[inline]
     pub fn get_reader(&self) -> &R {
-        &self.0
+        &self.reader
     }
 
     /// Get the underlying reader as a mutable reference
     #[inline]
     pub fn get_reader_mut(&mut self) -> &mut R {
-        &mut self.0
+        &mut self.reader
     }
 
     /// Parse the next item in the parser.
@@ -410,7 +438,7 @@ where
 
     async fn next_size(&mut self) -> Result<usize> {
         let mut buf = [0u8; 4];
-        self.0.read_exact(&mut buf).await?;
+        self.reader.read_exact(&mut buf).await?;
         Ok(buf.as_slice().read_u32::<BigEndian>()? as usize)
     }
 
@@ -421,9 +449,9 @@ where
         // without first getting the mark, and we can't get the size of the
         // mark from the prefix as some marks are recursive.
         let mut buf = [0u8; 1];
-        self.0.read_exact(&mut buf).await?;
+        self.reader.read_exact(&mut buf).await?;
         let prefix = Type::from_prefix(buf[0])?;
-        Ok(match prefix {
+        let mark = match prefix {
             Type::Long => Mark::Long,
             Type::Int => Mark::Int,
             Type::Short => Mark::Short,
@@ -448,7 +476,11 @@ where
                 Mark::Dict(len, Box::new(kmark), Box::new(vmark))
             }
             Type::Map => Mark::Map(self.next_size().await?),
-        })
+        };
+        if mark.checked_size().is_none() {
+            return Err(Error::data_error(format!("{} is too large", prefix)));
+        }
+        Ok(mark)
     }
 
     /// Skip the next value in the parser.
@@ -462,8 +494,14 @@ where
     /// see [Parser::skip_next()](crate::parser::Parser::skip_next)
     pub async fn skip_next(&mut self) -> Result<()> {
         let mark = self.next_mark().await?;
-        let mut buf = vec![0u8; mark.data_size()];
-        self.0.read_exact(&mut buf).await?;
+        let size = mark.data_size() as u64;
+
+        let mut sink = futures::io::sink();
+        let skipped = futures::io::copy((&mut self.reader).take(size), &mut sink).await?;
+        if skipped < size {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+
         Ok(())
     }
 
@@ -472,11 +510,27 @@ where
     /// see [Parser::next_value()](crate::parser::Parser::next_value)
     pub async fn next_value(&mut self) -> Result<Value> {
         let mark = self.next_mark().await?;
-        let mut buf = vec![0u8; mark.data_size()];
-        self.0.read_exact(&mut buf).await?;
-
-        let mut parser = Parser::from(&buf);
-        parser.next_data_value(&mark)
+        let size = mark.data_size();
+        if size > self.max_alloc {
+            return Err(Error::data_error(format!(
+                "{} needs {} bytes, which exceeds the limit of {} bytes",
+                mark.get_type(),
+                size,
+                self.max_alloc
+            )));
+        }
+        let mut buf = vec![0u8; size];
+        self.reader.read_exact(&mut buf).await?;
+
+        let mut parser = Parser::from(&buf).with_max_alloc(self.max_alloc);
+        match parser.next_data_value(&mark) {
+            // The whole value has already been read, so running out of data
+            // means the contents are larger than the mark claims.
+            Err(Error::IO(err)) if err.kind() == io::ErrorKind::UnexpectedEof => Err(
+                Error::data_error(format!("{} was larger than expected", mark.get_type())),
+            ),
+            res => res,
+        }
     }
 }
 
@@ -492,7 +546,7 @@ where
     /// see [Parser::seek_next()](crate::parser::Parser::seek_next)
     pub async fn seek_next(&mut self) -> Result<()> {
         let mark = self.next_mark().await?;
-        self.0
+        self.reader
             .seek(SeekFrom::Current(mark.data_size() as i64))
             .await?;
         Ok(())
@@ -535,6 +589,133 @@ mod test {
         .unwrap();
     }
 
+    const PARITY_DATA: &[&[u8]] = &[
+        b"l\x00\x30\x00\x00\x20\x10\x00\x05",
+        b"i\x03\x00\x00\x00",
+        b"h\x03\x00",
+        b"c\x03",
+        b"f\x00\x00\x00\x00",
+        b"d\x00\x00\x00\x00\x00\x00\x00\x00",
+        b"b\x00\x00\x00\x0bHello World",
+        b"s\x00\x00\x00\x0bHello World",
+        b"o\x00\x00\x00\x0bHello World",
+        b"ei\x00\x00\x00\x01\x00\x00\x00\xfe",
+        b"n",
+        b"ac\x00\x00\x00\x04\x01\x02\x03\x04",
+        b"aac\x00\x00\x00\x02\x00\x00\x00\x02\x01\x02\x03\x04",
+        b"A\x00\x00\x00\x08c\x01c\x02c\x03c\x04",
+        b"ms\x00\x00\x00\x01c\x00\x00\x00\x02a\x02b\x05",
+        b"M\x00\x00\x00\x10s\x00\x00\x00\x01ac\x01s\x00\x00\x00\x01bc\x02",
+        b"A\x00\x00\x00\x04c\x01i\x00\x00\x00\x00",
+        b"M\x00\x00\x00\x04c\x01i\x00\x00\x00\x00",
+        b"aaal\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff",
+        b"s\xff\xff\xff\xff",
+        b"x",
+    ];
+
+    // After an error the stream position is unspecified, so stop at the first
+    // error rather than comparing whatever follows it.
+    fn sync_values(data: &[u8]) -> Vec<Result<Value>> {
+        let mut parser = Parser::from(data);
+        let mut values = Vec::new();
+        while values.len() < 2 {
+            let value = parser.next_value();
+            let is_err = value.is_err();
+            values.push(value);
+            if is_err {
+                break;
+            }
+        }
+        values
+    }
+
+    async fn async_values(data: &[u8]) -> Vec<Result<Value>> {
+        let mut parser = AsyncParser::from(Cursor::new(data));
+        let mut values = Vec::new();
+        while values.len() < 2 {
+            let value = parser.next_value().await;
+            let is_err = value.is_err();
+            values.push(value);
+            if is_err {
+                break;
+            }
+        }
+        values
+    }
+
+    fn assert_same(sync: Result<Value>, asyn: Result<Value>) {
+        match (sync, asyn) {
+            (Ok(a), Ok(b)) => assert_eq!(a, b),
+            (Err(Error::IO(a)), Err(Error::IO(b))) => assert_eq!(a.kind(), b.kind()),
+            (Err(Error::DataError(_)), Err(Error::DataError(_))) => {}
+            (a, b) => panic!("sync {:?} != async {:?}", a, b),
+        }
+    }
+
+    #[test]
+    fn test_parse_parity() {
+        futures::executor::block_on(async {
+            for data in PARITY_DATA {
+                let mut buf = data.to_vec();
+                buf.extend_from_slice(b"c\x42");
+
+                let sync = sync_values(&buf);
+                let asyn = async_values(&buf).await;
+                assert_eq!(sync.len(), asyn.len());
+                for (s, a) in sync.into_iter().zip(asyn) {
+                    assert_same(s, a);
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn test_skip_parity() {
+        futures::executor::block_on(async {
+            for data in PARITY_DATA {
+                if Parser::from(data).next_value().is_err() {
+                    continue;
+                }
+
+                let mut buf = data.to_vec();
+                buf.extend_from_slice(b"c\x42");
+
+                let mut sync = Parser::from(std::io::Cursor::new(&buf));
+                sync.skip_next().unwrap();
+                let mut asyn = AsyncParser::from(Cursor::new(&buf));
+                asyn.skip_next().await.unwrap();
+                assert_same(sync.next_value(), asyn.next_value().await);
+
+                let mut sync = Parser::from(std::io::Cursor::new(&buf));
+                sync.seek_next().unwrap();
+                let mut asyn = AsyncParser::from(Cursor::new(&buf));
+                asyn.seek_next().await.unwrap();
+                assert_same(sync.next_value(), asyn.next_value().await);
+            }
+        });
+    }
+
+    #[test]
+    fn test_dump_parity() {
+        futures::executor::block_on(async {
+            for data in PARITY_DATA {
+                let value = match Parser::from(data).next_value() {
+                    Ok(value) => value,
+                    Err(_) => continue,
+                };
+
+                let mut dumper = Dumper::new();
+                dumper.write_value(&value).unwrap();
+
+                let mut async_dumper = AsyncDumper::from(Cursor::new(Vec::new()));
+                async_dumper.write_value(&value).unwrap();
+                async_dumper.flush().await.unwrap();
+
+                assert_eq!(dumper.writer(), async_dumper.writer().into_inner());
+            }
+        });
+    }
+
     #[test]
     fn test_skip() {
         futures::executor::block_on(async {
@@ -551,4 +732,47 @@ mod test {
         })
         .unwrap();
     }
+
+    #[test]
+    fn test_max_alloc() {
+        futures::executor::block_on(async {
+            let mut dumper = Dumper::new();
+            dumper.write_str("a".repeat(DEFAULT_MAX_ALLOC + 1)).unwrap();
+            let data = dumper.writer();
+
+            let mut parser = AsyncParser::from(Cursor::new(&data));
+            let err = parser.next_value().await.expect_err("DataError");
+            if let Error::DataError(_) = err {
+            } else {
+                panic!("Expected a DataError");
+            }
+
+            let mut parser =
+                AsyncParser::from(Cursor::new(&data)).with_max_alloc(DEFAULT_MAX_ALLOC * 2);
+            let val: String = parser.next().await.unwrap();
+            assert_eq!(val.len(), DEFAULT_MAX_ALLOC + 1);
+
+            // 500000 arrays of 500000 nulls, without any data
+            let mut parser = AsyncParser::from(Cursor::new(b"aan\x00\x07\xa1\x20\x00\x07\xa1\x20"));
+            let err = parser.next_value().await.expect_err("DataError");
+            if let Error::DataError(_) = err {
+            } else {
+                panic!("Expected a DataError");
+            }
+        });
+    }
+
+    #[test]
+    fn test_skip_huge() {
+        futures::executor::block_on(async {
+            let mut parser = AsyncParser::from(Cursor::new(b"s\xff\xff\xff\xffHello"));
+
+            let err = parser.skip_next().await.expect_err("UnexpectedEof");
+            if let Error::IO(e) = err {
+                assert_eq!(e.kind(), io::ErrorKind::UnexpectedEof);
+            } else {
+                panic!("Expected UnexpectedEof Error");
+            }
+        });
+    }
 }
@@ -20,6 +20,23 @@ pub mod ser;
 ///
 /// This is used as an intermidiate object for dumping/loading binary data. You
 /// will generally not need to use this struct.
+///
+/// There is no boolean value. A `bool` is stored as a [Value::Char] of `0` or
+/// `1`, and deserializing a `bool` accepts any integer, where non-zero is
+/// `true`. This works wherever the target type asks for a `bool`, including
+/// `Option<bool>` and bools nested in lists or structs. Deserializers that
+/// guess the type with `deserialize_any`, such as untagged enums, will see an
+/// `i8` instead.
+///
+/// ```
+/// use mbon::data::Value;
+///
+/// let val = Value::dump(vec![true, false]).unwrap();
+/// assert_eq!(val, Value::List(vec![Value::Char(1), Value::Char(0)]));
+///
+/// let bools: Vec<bool> = val.parse().unwrap();
+/// assert_eq!(bools, vec![true, false]);
+/// ```
 #[derive(Debug, Clone, PartialEq)]
 pub enum Value {
     Long(i64),
@@ -428,6 +445,47 @@ impl Mark {
         self.mark_size() + self.data_size()
     }
 
+    /// Get the size in bytes that the data will use in binary form, checking
+    /// for overflow
+    ///
+    /// A corrupt mark can describe more data than can be addressed. Where
+    /// [data_size()](Mark::data_size) would overflow, this returns `None`.
+    ///
+    /// ```
+    /// use mbon::data::Mark;
+    ///
+    /// assert_eq!(Mark::Int.checked_data_size(), Some(4));
+    ///
+    /// let huge = Mark::Array(usize::MAX, Box::new(Mark::Long));
+    /// assert_eq!(huge.checked_data_size(), None);
+    /// ```
+    pub fn checked_data_size(&self) -> Option<usize> {
+        match self {
+            Mark::Enum(m) => m.checked_data_size()?.checked_add(4),
+            Mark::Array(len, m) => len.checked_mul(m.checked_data_size()?),
+            Mark::Dict(len, k, v) => {
+                len.checked_mul(k.checked_data_size()?.checked_add(v.checked_data_size()?)?)
+            }
+            _ => Some(self.data_size()),
+        }
+    }
+
+    /// Get the total size in bytes that this value uses in binary form,
+    /// checking for overflow
+    ///
+    /// ```
+    /// use mbon::data::Mark;
+    ///
+    /// assert_eq!(Mark::Int.checked_size(), Some(5));
+    ///
+    /// let huge = Mark::Array(usize::MAX, Box::new(Mark::Long));
+    /// assert_eq!(huge.checked_size(), None);
+    /// ```
+    #[inline]
+    pub fn checked_size(&self) -> Option<usize> {
+        self.mark_size().checked_add(self.checked_data_size()?)
+    }
+
     /// Get the type of this mark
     pub fn get_type(&self) -> Type {
         match self {
@@ -449,6 +507,56 @@ impl Mark {
         }
     }
 
+    /// Check if two marks describe the same type, ignoring any sizes
+    ///
+    /// Lengths of strings, arrays, lists, dicts, and maps are ignored, but the
+    /// marks of nested items must still match.
+    ///
+    /// ```
+    /// use mbon::data::Mark;
+    ///
+    /// assert!(Mark::Str(4).type_eq(&Mark::Str(5)));
+    /// assert!(!Mark::Str(4).type_eq(&Mark::Bytes(4)));
+    ///
+    /// let a = Mark::Array(4, Box::new(Mark::Int));
+    /// assert!(a.type_eq(&Mark::Array(5, Box::new(Mark::Int))));
+    /// assert!(!a.type_eq(&Mark::Array(4, Box::new(Mark::Short))));
+    ///
+    /// let a = Mark::Array(2, Box::new(Mark::Str(3)));
+    /// assert!(a.type_eq(&Mark::Array(8, Box::new(Mark::Str(1)))));
+    ///
+    /// let d = Mark::Dict(3, Box::new(Mark::Str(1)), Box::new(Mark::Int));
+    /// assert!(d.type_eq(&Mark::Dict(1, Box::new(Mark::Str(4)), Box::new(Mark::Int))));
+    /// assert!(!d.type_eq(&Mark::Dict(3, Box::new(Mark::Str(1)), Box::new(Mark::Long))));
+    ///
+    /// let e = Mark::Enum(Box::new(Mark::Str(3)));
+    /// assert!(e.type_eq(&Mark::Enum(Box::new(Mark::Str(10)))));
+    /// assert!(!e.type_eq(&Mark::Enum(Box::new(Mark::Null))));
+    ///
+    /// assert!(Mark::Map(10).type_eq(&Mark::Map(42)));
+    /// assert!(!Mark::Map(10).type_eq(&Mark::List(10)));
+    /// ```
+    pub fn type_eq(&self, other: &Mark) -> bool {
+        match (self, other) {
+            (Mark::Long, Mark::Long)
+            | (Mark::Int, Mark::Int)
+            | (Mark::Short, Mark::Short)
+            | (Mark::Char, Mark::Char)
+            | (Mark::Float, Mark::Float)
+            | (Mark::Double, Mark::Double)
+            | (Mark::Bytes(_), Mark::Bytes(_))
+            | (Mark::Str(_), Mark::Str(_))
+            | (Mark::Object(_), Mark::Object(_))
+            | (Mark::Null, Mark::Null)
+            | (Mark::List(_), Mark::List(_))
+            | (Mark::Map(_), Mark::Map(_)) => true,
+            (Mark::Enum(a), Mark::Enum(b)) => a.type_eq(b),
+            (Mark::Array(_, a), Mark::Array(_, b)) => a.type_eq(b),
+            (Mark::Dict(_, ak, av), Mark::Dict(_, bk, bv)) => ak.type_eq(bk) && av.type_eq(bv),
+            _ => false,
+        }
+    }
+
     /// Get the mark from a value
     ///
     /// ```
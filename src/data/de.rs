@@ -15,8 +15,8 @@ pub struct ValueSeqAccess<'de> {
     index: usize,
 }
 pub struct ValueMapAccess<'de> {
-    seq: &'de Vec<(Value, Value)>,
-    index: usize,
+    seq: std::slice::Iter<'de, (Value, Value)>,
+    value: Option<&'de Value>,
 }
 pub struct ValueEnumAccess<'de> {
     parent: &'de Value,
@@ -39,8 +39,11 @@ impl<'de> ValueSeqAccess<'de> {
 
 impl<'de> ValueMapAccess<'de> {
     #[inline]
-    fn new(seq: &'de Vec<(Value, Value)>) -> Self {
-        Self { seq, index: 0 }
+    fn new(seq: &'de [(Value, Value)]) -> Self {
+        Self {
+            seq: seq.iter(),
+            value: None,
+        }
     }
 }
 
@@ -460,9 +463,13 @@ impl<'de> de::MapAccess<'de> for ValueMapAccess<'de> {
     where
         K: de::DeserializeSeed<'de>,
     {
-        Ok(if let Some((key, _value)) = self.seq.get(self.index) {
+        // Each key moves to the next pair, and its value is held until
+        // next_value_seed takes it.
+        Ok(if let Some((key, value)) = self.seq.next() {
+            self.value = Some(value);
             Some(seed.deserialize(ValueDe::new(key))?)
         } else {
+            self.value = None;
             None
         })
     }
@@ -471,13 +478,19 @@ impl<'de> de::MapAccess<'de> for ValueMapAccess<'de> {
     where
         V: de::DeserializeSeed<'de>,
     {
-        if let Some((_key, value)) = self.seq.get(self.index) {
-            self.index += 1;
+        if let Some(value) = self.value.take() {
             Ok(seed.deserialize(ValueDe::new(value))?)
         } else {
-            Err(Error::Msg("Expected index to be in bounds".into()))
+            Err(Error::Msg(
+                "next_value_seed called before next_key_seed".into(),
+            ))
         }
     }
+
+    #[inline]
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.seq.len())
+    }
 }
 
 impl<'de> de::EnumAccess<'de> for ValueEnumAccess<'de> {
@@ -530,9 +543,15 @@ impl<'de> de::VariantAccess<'de> for ValueEnumAccess<'de> {
 
 #[cfg(test)]
 mod test {
-    use serde::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+
+    use serde::{
+        de::{IgnoredAny, MapAccess},
+        Deserialize, Serialize,
+    };
 
-    use crate::{error::Error, parser::Parser};
+    use super::ValueMapAccess;
+    use crate::{data::Value, dumper::Dumper, error::Error, parser::Parser};
 
     #[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
     struct Foo {
@@ -622,4 +641,123 @@ mod test {
         let val: u8 = parser.next().unwrap();
         assert_eq!(val, 0x40);
     }
+
+    #[test]
+    fn test_map_of_maps() {
+        let mut map = BTreeMap::new();
+        for outer in ["a", "b", "c"] {
+            let inner: BTreeMap<String, i32> = ["x", "y", "z"]
+                .iter()
+                .enumerate()
+                .map(|(i, k)| (format!("{}{}", outer, k), i as i32))
+                .collect();
+            map.insert(outer.to_owned(), inner);
+        }
+
+        let mut dumper = Dumper::new();
+        dumper.write(&map).unwrap();
+        let data = dumper.writer();
+
+        let mut parser = Parser::from(&data);
+        let parsed: BTreeMap<String, BTreeMap<String, i32>> = parser.next().unwrap();
+        assert_eq!(parsed, map);
+    }
+
+    #[test]
+    fn test_map_access() {
+        let map = vec![
+            (Value::Str("a".into()), Value::Int(1)),
+            (Value::Str("b".into()), Value::Int(2)),
+        ];
+        let mut access = ValueMapAccess::new(&map);
+        assert_eq!(access.size_hint(), Some(2));
+
+        let err = access.next_value::<i32>().expect_err("Msg");
+        if let Error::Msg(_) = err {
+        } else {
+            panic!("Expected Error::Msg");
+        }
+
+        let key: String = access.next_key().unwrap().unwrap();
+        assert_eq!(key, "a");
+        let val: i32 = access.next_value().unwrap();
+        assert_eq!(val, 1);
+
+        let key: String = access.next_key().unwrap().unwrap();
+        assert_eq!(key, "b");
+        let val: i32 = access.next_value().unwrap();
+        assert_eq!(val, 2);
+
+        assert!(access.next_value::<IgnoredAny>().is_err());
+        assert!(access.next_key::<String>().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_bool_round_trip() {
+        let bools = vec![true, false, false, true];
+
+        let mut dumper = Dumper::new();
+        dumper.write(&bools).unwrap();
+        let data = dumper.writer();
+        assert_eq!(data, b"ac\x00\x00\x00\x04\x01\x00\x00\x01");
+
+        let mut parser = Parser::from(&data);
+        let parsed: Vec<bool> = parser.next().unwrap();
+        assert_eq!(parsed, bools);
+    }
+
+    #[test]
+    fn test_option_bool() {
+        let values = vec![Some(true), None, Some(false)];
+
+        let mut dumper = Dumper::new();
+        dumper.write(&values).unwrap();
+        let data = dumper.writer();
+
+        let mut parser = Parser::from(&data);
+        let parsed: Vec<Option<bool>> = parser.next().unwrap();
+        assert_eq!(parsed, values);
+    }
+
+    #[test]
+    fn test_nested_bool() {
+        let foos = vec![
+            Foo {
+                a: 1,
+                b: "a".to_owned(),
+                c: true,
+            },
+            Foo {
+                a: 2,
+                b: "b".to_owned(),
+                c: false,
+            },
+        ];
+
+        let mut dumper = Dumper::new();
+        dumper.write(&foos).unwrap();
+        let data = dumper.writer();
+
+        let mut parser = Parser::from(&data);
+        let parsed: Vec<Foo> = parser.next().unwrap();
+        assert_eq!(parsed, foos);
+    }
+
+    #[test]
+    fn test_bool_any() {
+        #[derive(Debug, Deserialize, PartialEq, Eq)]
+        #[serde(untagged)]
+        enum Any {
+            Bool(bool),
+            Char(i8),
+        }
+
+        let val = Value::dump(true).unwrap();
+        let any: Any = val.parse().unwrap();
+        assert_eq!(any, Any::Char(1));
+
+        let val = Value::Char(2);
+        let b: bool = val.parse().unwrap();
+        assert!(b);
+    }
 }
@@ -33,6 +33,8 @@ use crate::{
 /// * [`write_enum()`](Dumper::write_enum)
 /// * [`write_list()`](Dumper::write_list)
 /// * [`write_map()`](Dumper::write_map)
+/// * [`write_list_as()`](Dumper::write_list_as)
+/// * [`write_map_as()`](Dumper::write_map_as)
 #[derive(Debug)]
 pub struct Dumper<W>(W);
 
@@ -58,12 +60,31 @@ impl<W> AsMut<W> for Dumper<W> {
 }
 
 impl Dumper<Vec<u8>> {
+    /// Create a dumper that writes into an in-memory buffer
+    ///
+    /// The buffer can be retrieved with [`writer()`](Dumper::writer).
+    ///
+    /// ```
+    /// use mbon::dumper::Dumper;
+    ///
+    /// let mut dumper = Dumper::new();
+    /// dumper.write_int(0x20).unwrap();
+    ///
+    /// assert_eq!(dumper.writer(), b"i\x00\x00\x00\x20");
+    /// ```
     #[inline]
     pub fn new() -> Self {
         Self(Vec::new())
     }
 }
 
+impl Default for Dumper<Vec<u8>> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<W> Dumper<W>
 where
     W: Write,
@@ -621,14 +642,54 @@ where
     pub fn write_list(&mut self, val: impl AsRef<Vec<Value>>) -> Result<()> {
         let val = val.as_ref();
         if Value::can_be_array(val) {
-            self.write_mark_array(val.len(), Mark::from(val.first().unwrap()))?;
-            self.write_data_array(val)
+            self.write_array_form(val)
         } else {
-            self.write_mark_list(val.iter().map(|v| Mark::from(v).size()).sum())?;
-            self.write_data_list(val)
+            self.write_list_form(val)
         }
     }
 
+    /// Write a list of values to the dumper in a chosen form.
+    ///
+    /// `t` must be either [Type::Array] or [Type::List]. Unlike
+    /// [`write_list()`](Dumper::write_list), the form is not picked from the
+    /// values. Forcing an array fails if the values don't all share the same
+    /// mark, as is the case for an empty list. Nested lists and maps still
+    /// pick their own form.
+    ///
+    /// ```
+    /// use mbon::dumper::Dumper;
+    /// use mbon::data::{Type, Value};
+    ///
+    /// let mut dumper = Dumper::new();
+    /// dumper.write_list_as(vec![Value::Char(0x10), Value::Char(0x20)], Type::List);
+    ///
+    /// assert_eq!(dumper.writer(), b"A\x00\x00\x00\x04c\x10c\x20");
+    ///
+    /// let mut dumper = Dumper::new();
+    /// let res = dumper.write_list_as(vec![Value::Char(0x10), Value::Short(0x20)], Type::Array);
+    ///
+    /// assert!(res.is_err());
+    /// ```
+    pub fn write_list_as(&mut self, val: impl AsRef<Vec<Value>>, t: Type) -> Result<()> {
+        let val = val.as_ref();
+        match t {
+            Type::Array if Value::can_be_array(val) => self.write_array_form(val),
+            Type::Array => Err(Error::data_error("List cannot be stored as an array")),
+            Type::List => self.write_list_form(val),
+            _ => Err(Error::msg(format!("A list cannot be written as {}", t))),
+        }
+    }
+
+    fn write_array_form(&mut self, val: &[Value]) -> Result<()> {
+        self.write_mark_array(val.len(), Mark::from(val.first().unwrap()))?;
+        self.write_data_array(val)
+    }
+
+    fn write_list_form(&mut self, val: &[Value]) -> Result<()> {
+        self.write_mark_list(val.iter().map(|v| Mark::from(v).size()).sum())?;
+        self.write_data_list(val)
+    }
+
     /// Write a key, value map of values to the dumper.
     ///
     /// This can be written in two forms:
@@ -666,15 +727,58 @@ where
     pub fn write_map(&mut self, val: impl AsRef<Vec<(Value, Value)>>) -> Result<()> {
         let val = val.as_ref();
         if Value::can_be_dict(val) {
-            let (k, v) = val.first().unwrap();
-            self.write_mark_dict(val.len(), Mark::from(k), Mark::from(v))?;
-            self.write_data_dict(val)
+            self.write_dict_form(val)
         } else {
-            self.write_mark_map(val.iter().map(|(k, v)| k.size() + v.size()).sum())?;
-            self.write_data_map(val)
+            self.write_map_form(val)
+        }
+    }
+
+    /// Write a key, value map of values to the dumper in a chosen form.
+    ///
+    /// `t` must be either [Type::Dict] or [Type::Map]. Unlike
+    /// [`write_map()`](Dumper::write_map), the form is not picked from the
+    /// pairs. Forcing a dict fails if the pairs don't all share the same key
+    /// and value marks, as is the case for an empty map. Nested lists and maps
+    /// still pick their own form.
+    ///
+    /// ```
+    /// use mbon::dumper::Dumper;
+    /// use mbon::data::{Type, Value};
+    ///
+    /// let mut dumper = Dumper::new();
+    /// dumper.write_map_as(vec![(Value::Char(0x01), Value::Char(0x10))], Type::Map);
+    ///
+    /// assert_eq!(dumper.writer(), b"M\x00\x00\x00\x04c\x01c\x10");
+    ///
+    /// let mut dumper = Dumper::new();
+    /// let res = dumper.write_map_as(vec![
+    ///     (Value::Char(0x01), Value::Char(0x10)),
+    ///     (Value::Char(0x02), Value::Short(0x20)),
+    /// ], Type::Dict);
+    ///
+    /// assert!(res.is_err());
+    /// ```
+    pub fn write_map_as(&mut self, val: impl AsRef<Vec<(Value, Value)>>, t: Type) -> Result<()> {
+        let val = val.as_ref();
+        match t {
+            Type::Dict if Value::can_be_dict(val) => self.write_dict_form(val),
+            Type::Dict => Err(Error::data_error("Map cannot be stored as a dict")),
+            Type::Map => self.write_map_form(val),
+            _ => Err(Error::msg(format!("A map cannot be written as {}", t))),
         }
     }
 
+    fn write_dict_form(&mut self, val: &[(Value, Value)]) -> Result<()> {
+        let (k, v) = val.first().unwrap();
+        self.write_mark_dict(val.len(), Mark::from(k), Mark::from(v))?;
+        self.write_data_dict(val)
+    }
+
+    fn write_map_form(&mut self, val: &[(Value, Value)]) -> Result<()> {
+        self.write_mark_map(val.iter().map(|(k, v)| k.size() + v.size()).sum())?;
+        self.write_data_map(val)
+    }
+
     /// Write any value to the dumper.
     ///
     /// This will call the appropriate function for the given value type.
@@ -895,6 +999,13 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_default() {
+        let mut dumper = Dumper::default();
+        dumper.write(&"hi").unwrap();
+        assert_eq!(dumper.writer(), b"s\x00\x00\x00\x02hi");
+    }
+
     #[test]
     fn test_dict() {
         let mut dumper = Dumper::new();
@@ -905,4 +1016,83 @@ mod test {
         dumper.write_map(&value).unwrap();
         assert_eq!(dumper.0, b"ms\x00\x00\x00\x01c\x00\x00\x00\x02a\x02b\x05");
     }
+
+    #[test]
+    fn test_list_as_list() {
+        let mut dumper = Dumper::new();
+        let value = vec![Value::Char(2), Value::Char(5)];
+        dumper.write_list_as(&value, Type::List).unwrap();
+        assert_eq!(dumper.0, b"A\x00\x00\x00\x04c\x02c\x05");
+    }
+
+    #[test]
+    fn test_list_as_array() {
+        let mut dumper = Dumper::new();
+        let value = vec![Value::Char(2), Value::Char(5)];
+        dumper.write_list_as(&value, Type::Array).unwrap();
+        assert_eq!(dumper.0, b"ac\x00\x00\x00\x02\x02\x05");
+
+        let value = vec![Value::Char(2), Value::Short(5)];
+        let err = dumper
+            .write_list_as(&value, Type::Array)
+            .expect_err("DataError");
+        if let Error::DataError(_) = err {
+        } else {
+            panic!("Expected a DataError");
+        }
+
+        let err = dumper
+            .write_list_as(vec![], Type::Array)
+            .expect_err("DataError");
+        if let Error::DataError(_) = err {
+        } else {
+            panic!("Expected a DataError");
+        }
+    }
+
+    #[test]
+    fn test_map_as_map() {
+        let mut dumper = Dumper::new();
+        let value = vec![
+            (Value::Str("a".into()), Value::Char(2)),
+            (Value::Str("b".into()), Value::Char(5)),
+        ];
+        dumper.write_map_as(&value, Type::Map).unwrap();
+        assert_eq!(
+            dumper.0,
+            b"M\x00\x00\x00\x10s\x00\x00\x00\x01ac\x02s\x00\x00\x00\x01bc\x05"
+        );
+    }
+
+    #[test]
+    fn test_map_as_dict() {
+        let mut dumper = Dumper::new();
+        let value = vec![
+            (Value::Str("a".into()), Value::Char(2)),
+            (Value::Str("b".into()), Value::Char(5)),
+        ];
+        dumper.write_map_as(&value, Type::Dict).unwrap();
+        assert_eq!(dumper.0, b"ms\x00\x00\x00\x01c\x00\x00\x00\x02a\x02b\x05");
+
+        let value = vec![
+            (Value::Str("a".into()), Value::Char(2)),
+            (Value::Str("b".into()), Value::Short(5)),
+        ];
+        let err = dumper
+            .write_map_as(&value, Type::Dict)
+            .expect_err("DataError");
+        if let Error::DataError(_) = err {
+        } else {
+            panic!("Expected a DataError");
+        }
+    }
+
+    #[test]
+    fn test_list_as_bad_type() {
+        let mut dumper = Dumper::new();
+        let value = vec![Value::Char(2)];
+        assert!(dumper.write_list_as(&value, Type::Map).is_err());
+        assert!(dumper.write_map_as(vec![], Type::List).is_err());
+        assert!(dumper.0.is_empty());
+    }
 }
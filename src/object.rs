@@ -2,6 +2,13 @@
 //!
 //! You can implement [ObjectParse] and [ObjectDump] to allow for custom object
 //! dumping and parsing.
+//!
+//! Large objects can implement [ObjectParseStreaming] instead of
+//! [ObjectParse] to be parsed without reading the whole object into memory.
+
+use std::io::Read;
+
+use crate::parser::Parser;
 
 /// A loader that can load a struct from a binary object.
 ///
@@ -42,6 +49,60 @@ where
     fn parse_object(object: &[u8]) -> Result<Self, Self::Error>;
 }
 
+/// A loader that can load a struct from a binary object as it is read.
+///
+/// Unlike [ObjectParse], the object is not read into memory first. The struct
+/// is given a parser that is limited to the body of the object along with the
+/// length of the body. Anything that is not read is skipped by
+/// [`Parser::next_obj_streaming()`].
+///
+/// ```
+/// use mbon::object::ObjectParseStreaming;
+/// use mbon::parser::Parser;
+/// use mbon::error::Error;
+/// use std::io::Read;
+///
+/// struct Header {
+///     version: u32,
+/// }
+///
+/// impl ObjectParseStreaming for Header {
+///     type Error = Error;
+///
+///     fn parse_object_streaming<R: Read>(
+///         parser: &mut Parser<R>,
+///         _len: usize,
+///     ) -> Result<Self, Self::Error> {
+///         let version = parser.next()?;
+///         Ok(Self { version })
+///     }
+/// }
+///
+/// let mut parser = Parser::from(
+///     b"o\x00\x00\x00\x0ei\x00\x00\x00\x02s\x00\x00\x00\x04Bodyc\x01"
+/// );
+///
+/// let header: Header = parser.next_obj_streaming().unwrap();
+/// assert_eq!(header.version, 2);
+///
+/// let val: i8 = parser.next().unwrap();
+/// assert_eq!(val, 1);
+/// ```
+pub trait ObjectParseStreaming
+where
+    Self: Sized,
+{
+    type Error;
+
+    /// Load from a binary object as it is read
+    ///
+    /// `parser` starts at the body of the object and ends after `len` bytes.
+    fn parse_object_streaming<R: Read>(
+        parser: &mut Parser<R>,
+        len: usize,
+    ) -> Result<Self, Self::Error>;
+}
+
 /// A dumper that can dump a binary object from a struct.
 ///
 /// A possible use case is to store a struct more efficiently than a map
@@ -150,4 +211,74 @@ mod test {
 
         assert_eq!(dumper.writer(), data);
     }
+
+    #[derive(Debug)]
+    struct FirstField(String);
+
+    impl ObjectParseStreaming for FirstField {
+        type Error = Error;
+
+        fn parse_object_streaming<R: Read>(
+            parser: &mut Parser<R>,
+            _len: usize,
+        ) -> Result<Self, Error> {
+            Ok(Self(parser.next()?))
+        }
+    }
+
+    #[test]
+    fn test_deserialize_streaming() {
+        let mut dumper = Dumper::new();
+        dumper.write(&"Hello").unwrap();
+        dumper.write_bytes(vec![0xff; 0x100000]).unwrap();
+        let object = dumper.writer();
+
+        let mut dumper = Dumper::new();
+        dumper.write_object(&object).unwrap();
+        dumper.write(&0x4030).unwrap();
+        let data = dumper.writer();
+
+        // Only the first field fits within the limit, so the rest must be
+        // skipped rather than read.
+        let mut parser = Parser::from(&data).with_max_alloc(0x100);
+        let first: FirstField = parser.next_obj_streaming().unwrap();
+        assert_eq!(first.0, "Hello");
+        assert_eq!(parser.offset(), 5 + object.len() as u64);
+
+        let val: i32 = parser.next().unwrap();
+        assert_eq!(val, 0x4030);
+    }
+
+    #[test]
+    fn test_deserialize_streaming_bounds() {
+        // The string is cut off by the end of the object, so the parser must
+        // not read the value after it.
+        let mut parser = Parser::from(b"o\x00\x00\x00\x06s\x00\x00\x00\x05Hc\x01");
+        let err = parser
+            .next_obj_streaming::<FirstField>()
+            .expect_err("Error");
+        if let Error::Msg(_) = err {
+        } else {
+            panic!("Expected Error::Msg");
+        }
+
+        let val: i8 = parser.next().unwrap();
+        assert_eq!(val, 1);
+    }
+
+    #[test]
+    fn test_deserialize_streaming_offset() {
+        let mut parser = Parser::from(b"c\x01o\x00\x00\x00\x06s\x00\x00\x00\x01\xff");
+        parser.next_value().unwrap();
+
+        let err = parser
+            .next_obj_streaming::<FirstField>()
+            .expect_err("Error");
+        if let Error::Msg(msg) = err {
+            assert!(msg.contains("at byte 0x7: "), "{}", msg);
+        } else {
+            panic!("Expected Error::Msg");
+        }
+        assert_eq!(parser.offset(), 13);
+    }
 }
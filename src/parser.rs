@@ -5,29 +5,157 @@
 use crate::{
     data::{Mark, Type, Value},
     error::{Error, Result},
-    object::ObjectParse,
+    object::{ObjectParse, ObjectParseStreaming},
 };
 use byteorder::{BigEndian, ReadBytesExt};
 use serde::de::DeserializeOwned;
 
-use std::io::{Read, Seek, SeekFrom};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::iter::FusedIterator;
+use std::mem;
 
 /// A struct that parses binary data from a bytearray
 ///
 /// You can deserialize data using
 /// * [`next()`](Parser::next)
 /// * [`next_obj()`](Parser::next_obj)
+/// * [`next_obj_streaming()`](Parser::next_obj_streaming)
 ///
 /// Or you can deserialize data directly using
 /// * [`next_value()`](Parser::next_value)
-pub struct Parser<R>(R);
+///
+/// Marks are read a few bytes at a time, so every value results in several
+/// small reads. If the reader is unbuffered, such as a [std::fs::File], each
+/// of those reads is a separate syscall. Wrap such readers in a
+/// [std::io::BufReader] before parsing.
+///
+/// ```no_run
+/// use std::fs::File;
+/// use std::io::BufReader;
+///
+/// use mbon::parser::Parser;
+///
+/// let file = File::open("data.mbon").unwrap();
+/// let mut parser = Parser::from(BufReader::new(file));
+///
+/// let val: u32 = parser.next().unwrap();
+/// ```
+///
+/// Lengths are read from the data itself, so a corrupt or malicious value
+/// could ask for a huge allocation. The parser refuses to allocate more than
+/// [`max_alloc()`](Parser::max_alloc) bytes for a single value, which is
+/// [DEFAULT_MAX_ALLOC] unless changed with
+/// [`with_max_alloc()`](Parser::with_max_alloc).
+///
+/// The parser counts the bytes it consumes, and data errors include the offset
+/// of the value that failed, e.g. `at byte 0x2: Unknown prefix`.
+pub struct Parser<R> {
+    reader: R,
+    max_alloc: usize,
+    offset: u64,
+    item_start: u64,
+}
+
+/// A reader that counts the bytes read into the parser's offset
+struct Counted<'a, R> {
+    reader: &'a mut R,
+    offset: &'a mut u64,
+}
+
+impl<R> Read for Counted<'_, R>
+where
+    R: Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.reader.read(buf)?;
+        *self.offset += n as u64;
+        Ok(n)
+    }
+}
+
+/// The default limit for a single allocation made by a [Parser]
+pub const DEFAULT_MAX_ALLOC: usize = 16 * 1024 * 1024;
+
+impl<R> Parser<R> {
+    #[inline]
+    fn new(reader: R) -> Self {
+        Self {
+            reader,
+            max_alloc: DEFAULT_MAX_ALLOC,
+            offset: 0,
+            item_start: 0,
+        }
+    }
+
+    /// Set the largest allocation the parser may make for a single value
+    ///
+    /// Values that would need more than `max_alloc` bytes will fail with a
+    /// DataError instead of being read.
+    ///
+    /// ```
+    /// use mbon::parser::Parser;
+    ///
+    /// let mut parser = Parser::from(b"s\x00\x00\x00\x05Hello").with_max_alloc(4);
+    /// assert!(parser.next_value().is_err());
+    ///
+    /// let mut parser = Parser::from(b"s\x00\x00\x00\x05Hello").with_max_alloc(5);
+    /// let val: String = parser.next().unwrap();
+    /// assert_eq!(val, "Hello");
+    /// ```
+    #[inline]
+    pub fn with_max_alloc(mut self, max_alloc: usize) -> Self {
+        self.max_alloc = max_alloc;
+        self
+    }
+
+    /// Get the largest allocation the parser may make for a single value
+    #[inline]
+    pub fn max_alloc(&self) -> usize {
+        self.max_alloc
+    }
+
+    /// Get the number of bytes the parser has consumed
+    ///
+    /// ```
+    /// use mbon::parser::Parser;
+    ///
+    /// let mut parser = Parser::from(b"c\x01i\x00\x00\x00\x02");
+    /// assert_eq!(parser.offset(), 0);
+    ///
+    /// let _: i8 = parser.next().unwrap();
+    /// assert_eq!(parser.offset(), 2);
+    /// ```
+    #[inline]
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    #[inline]
+    fn counted(&mut self) -> Counted<'_, R> {
+        Counted {
+            reader: &mut self.reader,
+            offset: &mut self.offset,
+        }
+    }
+
+    /// Add the start of the failing value to a data error
+    fn locate<T>(&self, res: Result<T>) -> Result<T> {
+        match res {
+            Err(Error::DataError(msg)) => Err(Error::DataError(format!(
+                "at byte {:#x}: {}",
+                self.item_start, msg
+            ))),
+            res => res,
+        }
+    }
+}
 
 impl<'a, T> From<&'a T> for Parser<&'a [u8]>
 where
     T: AsRef<[u8]>,
 {
     fn from(slice: &'a T) -> Self {
-        Self(slice.as_ref())
+        Self::new(slice.as_ref())
     }
 }
 
@@ -36,19 +164,19 @@ where
     R: Read,
 {
     fn from(reader: R) -> Self {
-        Self(reader)
+        Self::new(reader)
     }
 }
 
 impl<R> AsRef<R> for Parser<R> {
     fn as_ref(&self) -> &R {
-        &self.0
+        &self.reader
     }
 }
 
 impl<R> AsMut<R> for Parser<R> {
     fn as_mut(&mut self) -> &mut R {
-        &mut self.0
+        &mut self.reader
     }
 }
 
@@ -59,19 +187,19 @@ where
     /// Turn the parser into the underlying reader
     #[inline]
     pub fn reader(self) -> R {
-        self.0
+        self.reader
     }
 
     /// Get the the underlying reader as a reference
     #[inline]
     pub fn get_reader(&self) -> &R {
-        &self.0
+        &self.reader
     }
 
     /// Get the the underlying reader as a mutable reference
     #[inline]
     pub fn get_reader_mut(&mut self) -> &mut R {
-        &mut self.0
+        &mut self.reader
     }
 
     /// Parse the next item in the parser.
@@ -146,55 +274,129 @@ where
         self.next_value()?.parse_obj()
     }
 
+    /// Parse the next custom object in the parser without reading it into
+    /// memory first.
+    ///
+    /// This is like [`next_obj()`](Parser::next_obj), but the object is
+    /// parsed with [ObjectParseStreaming] while it is being read. Whatever
+    /// part of the object is not read by the implementation is skipped.
+    ///
+    /// Note: the next value in the parser must be an Object
+    ///
+    /// See [ObjectParseStreaming] for an example.
+    pub fn next_obj_streaming<T>(&mut self) -> Result<T>
+    where
+        T: ObjectParseStreaming,
+        <T as ObjectParseStreaming>::Error: std::error::Error + 'static,
+    {
+        let mark = self.next_value_mark();
+        let mark = self.locate(mark)?;
+        let len = match mark {
+            Mark::Object(len) => len,
+            mark => {
+                self.skip_data(mark.data_size() as u64)?;
+                return Err(Error::Expected(Type::Object));
+            }
+        };
+
+        let max_alloc = self.max_alloc;
+        let offset = self.offset;
+        let mut body = (&mut self.reader).take(len as u64);
+
+        // Count from the start of the body so errors use stream offsets
+        let mut parser = Parser::new(&mut body).with_max_alloc(max_alloc);
+        parser.offset = offset;
+        let res = T::parse_object_streaming(&mut parser, len);
+        io::copy(&mut body, &mut io::sink())?;
+
+        let remaining = body.limit();
+        self.offset += len as u64 - remaining;
+        if remaining > 0 {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+
+        Error::from_res(res)
+    }
+
     #[inline]
     fn next_type(&mut self) -> Result<Type> {
-        Type::from_prefix(self.0.read_u8()?)
+        Type::from_prefix(self.counted().read_u8()?)
     }
 
-    fn next_data_n(&mut self, n: usize) -> Result<Vec<u8>> {
+    fn check_alloc(&self, size: usize, t: Type) -> Result<()> {
+        if size > self.max_alloc {
+            return Err(Error::data_error(format!(
+                "{} needs {} bytes, which exceeds the limit of {} bytes",
+                t, size, self.max_alloc
+            )));
+        }
+        Ok(())
+    }
+
+    /// Create a vec for `len` items of `marks` without trusting `len` for the
+    /// capacity
+    fn alloc_vec<T>(&self, len: usize, marks: &[&Mark], t: Type) -> Result<Vec<T>> {
+        let max_len = self.max_alloc / mem::size_of::<T>().max(1);
+        if marks.iter().all(|m| m.data_size() == 0) {
+            // Nothing is read for these items, so the vec and any containers
+            // nested in it would grow to their full length no matter how much
+            // data is actually available.
+            let size = empty_items_alloc(len, mem::size_of::<T>(), marks);
+            self.check_alloc(size.unwrap_or(usize::MAX), t)?;
+        }
+        Ok(Vec::with_capacity(len.min(max_len)))
+    }
+
+    fn next_data_n(&mut self, n: usize, t: Type) -> Result<Vec<u8>> {
+        self.check_alloc(n, t)?;
         let mut buf = vec![0; n];
-        self.0.read_exact(&mut buf)?;
+        self.counted().read_exact(&mut buf)?;
         Ok(buf)
     }
 
+    #[inline]
+    fn next_size(&mut self) -> Result<usize> {
+        Ok(self.counted().read_u32::<BigEndian>()? as usize)
+    }
+
     #[inline]
     fn next_data_long(&mut self) -> Result<i64> {
-        Ok(self.0.read_i64::<BigEndian>()?)
+        Ok(self.counted().read_i64::<BigEndian>()?)
     }
 
     #[inline]
     fn next_data_int(&mut self) -> Result<i32> {
-        Ok(self.0.read_i32::<BigEndian>()?)
+        Ok(self.counted().read_i32::<BigEndian>()?)
     }
 
     #[inline]
     fn next_data_short(&mut self) -> Result<i16> {
-        Ok(self.0.read_i16::<BigEndian>()?)
+        Ok(self.counted().read_i16::<BigEndian>()?)
     }
 
     #[inline]
     fn next_data_char(&mut self) -> Result<i8> {
-        Ok(self.0.read_i8()?)
+        Ok(self.counted().read_i8()?)
     }
 
     #[inline]
     fn next_data_float(&mut self) -> Result<f32> {
-        Ok(self.0.read_f32::<BigEndian>()?)
+        Ok(self.counted().read_f32::<BigEndian>()?)
     }
 
     #[inline]
     fn next_data_double(&mut self) -> Result<f64> {
-        Ok(self.0.read_f64::<BigEndian>()?)
+        Ok(self.counted().read_f64::<BigEndian>()?)
     }
 
     #[inline]
     fn next_data_bytes(&mut self, n: usize) -> Result<Vec<u8>> {
-        self.next_data_n(n)
+        self.next_data_n(n, Type::Bytes)
     }
 
     #[inline]
     fn next_data_str(&mut self, n: usize) -> Result<String> {
-        let buf = self.next_data_n(n)?;
+        let buf = self.next_data_n(n, Type::Str)?;
         Ok(String::from_utf8(buf)?)
     }
 
@@ -205,7 +407,7 @@ where
     }
 
     fn next_data_array(&mut self, len: usize, t: &Mark) -> Result<Vec<Value>> {
-        let mut arr = Vec::with_capacity(len);
+        let mut arr = self.alloc_vec(len, &[t], Type::Array)?;
 
         for _ in 0..len {
             let v = self.next_data_value(t)?;
@@ -216,18 +418,20 @@ where
     }
 
     fn next_data_list(&mut self, size: usize) -> Result<Vec<Value>> {
+        let start = self.item_start;
         let mut arr = Vec::new();
 
         let mut read = 0;
 
         while read < size {
-            let m = self.next_mark()?;
+            let m = self.next_value_mark()?;
             let v = self.next_data_value(&m)?;
             arr.push(v);
             read += m.size();
         }
 
         if read > size {
+            self.item_start = start;
             return Err(Error::data_error("List was larger than expected"));
         }
 
@@ -235,7 +439,7 @@ where
     }
 
     fn next_data_dict(&mut self, len: usize, k: &Mark, v: &Mark) -> Result<Vec<(Value, Value)>> {
-        let mut arr = Vec::with_capacity(len);
+        let mut arr = self.alloc_vec(len, &[k, v], Type::Dict)?;
 
         for _ in 0..len {
             let key = self.next_data_value(k)?;
@@ -247,13 +451,14 @@ where
     }
 
     fn next_data_map(&mut self, size: usize) -> Result<Vec<(Value, Value)>> {
+        let start = self.item_start;
         let mut arr = Vec::new();
         let mut read = 0;
 
         while read < size {
-            let k = self.next_mark()?;
+            let k = self.next_value_mark()?;
             let key = self.next_data_value(&k)?;
-            let v = self.next_mark()?;
+            let v = self.next_value_mark()?;
             let val = self.next_data_value(&v)?;
 
             arr.push((key, val));
@@ -261,6 +466,7 @@ where
         }
 
         if read > size {
+            self.item_start = start;
             return Err(Error::data_error("Map was larger than expected"));
         }
 
@@ -277,7 +483,7 @@ where
             Mark::Double => Value::Double(self.next_data_double()?),
             Mark::Bytes(n) => Value::Bytes(self.next_data_bytes(*n)?),
             Mark::Str(n) => Value::Str(self.next_data_str(*n)?.to_owned()),
-            Mark::Object(n) => Value::Object(self.next_data_bytes(*n)?.to_vec()),
+            Mark::Object(n) => Value::Object(self.next_data_n(*n, Type::Object)?),
             Mark::Enum(m) => {
                 let (var, val) = self.next_data_enum(&m)?;
                 Value::Enum(var, Box::new(val))
@@ -290,34 +496,50 @@ where
         })
     }
 
+    #[inline]
     fn next_mark(&mut self) -> Result<Mark> {
         let t = self.next_type()?;
-        Ok(match t {
+        self.next_mark_of(t)
+    }
+
+    /// Read the mark of a value, recording where the value starts for errors
+    #[inline]
+    fn next_value_mark(&mut self) -> Result<Mark> {
+        self.item_start = self.offset;
+        self.next_mark()
+    }
+
+    fn next_mark_of(&mut self, t: Type) -> Result<Mark> {
+        let mark = match t {
             Type::Long => Mark::Long,
             Type::Int => Mark::Int,
             Type::Short => Mark::Short,
             Type::Char => Mark::Char,
             Type::Float => Mark::Float,
             Type::Double => Mark::Double,
-            Type::Bytes => Mark::Bytes(self.next_data_int()? as usize),
-            Type::Str => Mark::Str(self.next_data_int()? as usize),
-            Type::Object => Mark::Object(self.next_data_int()? as usize),
+            Type::Bytes => Mark::Bytes(self.next_size()?),
+            Type::Str => Mark::Str(self.next_size()?),
+            Type::Object => Mark::Object(self.next_size()?),
             Type::Enum => Mark::Enum(Box::new(self.next_mark()?)),
             Type::Null => Mark::Null,
             Type::Array => {
                 let mark = self.next_mark()?;
-                let len = self.next_data_int()? as usize;
+                let len = self.next_size()?;
                 Mark::Array(len, Box::new(mark))
             }
-            Type::List => Mark::List(self.next_data_int()? as usize),
+            Type::List => Mark::List(self.next_size()?),
             Type::Dict => {
                 let k = self.next_mark()?;
                 let v = self.next_mark()?;
-                let len = self.next_data_int()? as usize;
+                let len = self.next_size()?;
                 Mark::Dict(len, Box::new(k), Box::new(v))
             }
-            Type::Map => Mark::Map(self.next_data_int()? as usize),
-        })
+            Type::Map => Mark::Map(self.next_size()?),
+        };
+        if mark.checked_size().is_none() {
+            return Err(Error::data_error(format!("{} is too large", t)));
+        }
+        Ok(mark)
     }
 
     /// Skip the next value in the parser.
@@ -343,10 +565,16 @@ where
     /// assert_eq!(v, 0x42);
     /// ```
     pub fn skip_next(&mut self) -> Result<()> {
-        let mark = self.next_mark()?;
-        let size = mark.data_size();
+        let mark = self.next_value_mark();
+        let mark = self.locate(mark)?;
+        self.skip_data(mark.data_size() as u64)
+    }
 
-        self.next_data_n(size)?;
+    fn skip_data(&mut self, size: u64) -> Result<()> {
+        let skipped = io::copy(&mut self.counted().take(size), &mut io::sink())?;
+        if skipped < size {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
 
         Ok(())
     }
@@ -367,8 +595,55 @@ where
     /// ```
     #[inline]
     pub fn next_value(&mut self) -> Result<Value> {
-        let mark = self.next_mark()?;
-        self.next_data_value(&mark)
+        let value = self
+            .next_value_mark()
+            .and_then(|mark| self.next_data_value(&mark));
+        self.locate(value)
+    }
+
+    /// Parse the next value, or `None` if the reader ends before the value
+    /// starts.
+    fn next_value_or_end(&mut self) -> Result<Option<Value>> {
+        self.item_start = self.offset;
+        let mut prefix = [0u8; 1];
+        loop {
+            match self.counted().read(&mut prefix) {
+                Ok(0) => return Ok(None),
+                Ok(_) => break,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        let value = Type::from_prefix(prefix[0])
+            .and_then(|t| self.next_mark_of(t))
+            .and_then(|mark| self.next_data_value(&mark));
+        Ok(Some(self.locate(value)?))
+    }
+
+    /// Iterate over the remaining values in the parser.
+    ///
+    /// The iterator ends when the reader runs out of data between two values.
+    /// If the data ends in the middle of a value, or any other error occurs,
+    /// the error is yielded and the iterator ends.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use mbon::parser::Parser;
+    /// use mbon::data::Value;
+    ///
+    /// let parser = Parser::from(b"i\x00\x00\x00\x42c\x10n");
+    /// let values: Vec<Value> = parser.values().collect::<Result<_, _>>().unwrap();
+    ///
+    /// assert_eq!(values, vec![Value::Int(0x42), Value::Char(0x10), Value::Null]);
+    /// ```
+    #[inline]
+    pub fn values(self) -> Values<R> {
+        Values {
+            parser: self,
+            done: false,
+        }
     }
 }
 
@@ -399,15 +674,129 @@ where
     /// assert_eq!(val, 32);
     /// ```
     pub fn seek_next(&mut self) -> Result<()> {
-        let mark = self.next_mark()?;
-        let size = mark.data_size();
+        let mark = self.next_value_mark();
+        let size = self.locate(mark)?.data_size();
 
-        self.0.seek(SeekFrom::Current(size as i64))?;
+        self.reader.seek(SeekFrom::Current(size as i64))?;
+        self.offset += size as u64;
 
         Ok(())
     }
+
+    /// Get the type of the next value without consuming it.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use mbon::parser::Parser;
+    /// use mbon::data::Type;
+    /// use std::io::Cursor;
+    ///
+    /// let mut parser = Parser::from(Cursor::new(b"s\x00\x00\x00\x05Hello"));
+    ///
+    /// assert_eq!(parser.peek_type().unwrap(), Type::Str);
+    ///
+    /// let val: String = parser.next().unwrap();
+    /// assert_eq!(val, "Hello");
+    /// ```
+    pub fn peek_type(&mut self) -> Result<Type> {
+        let prefix = self.reader.read_u8()?;
+        self.reader.seek(SeekFrom::Current(-1))?;
+        Type::from_prefix(prefix)
+    }
+
+    /// Get the mark of the next value without consuming it.
+    ///
+    /// The reader is returned to the start of the value even if the mark could
+    /// not be parsed.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// use mbon::parser::Parser;
+    /// use mbon::data::Mark;
+    /// use std::io::Cursor;
+    ///
+    /// let mut parser = Parser::from(Cursor::new(b"s\x00\x00\x00\x05Hello"));
+    ///
+    /// assert_eq!(parser.peek_mark().unwrap(), Mark::Str(5));
+    ///
+    /// let val: String = parser.next().unwrap();
+    /// assert_eq!(val, "Hello");
+    /// ```
+    pub fn peek_mark(&mut self) -> Result<Mark> {
+        let start = self.reader.stream_position()?;
+        let offset = self.offset;
+        let mark = self.next_value_mark();
+        let mark = self.locate(mark);
+        self.offset = offset;
+        self.reader.seek(SeekFrom::Start(start))?;
+        mark
+    }
+}
+
+/// Get the bytes allocated for `len` items of `marks` which have no data,
+/// where each item takes `slot` bytes in its vec
+fn empty_items_alloc(len: usize, slot: usize, marks: &[&Mark]) -> Option<usize> {
+    let mut item = slot;
+    for mark in marks {
+        item = item.checked_add(empty_value_alloc(mark)?)?;
+    }
+    len.checked_mul(item)
+}
+
+/// Get the bytes allocated for the contents of a value which has no data
+fn empty_value_alloc(mark: &Mark) -> Option<usize> {
+    match mark {
+        Mark::Enum(m) => mem::size_of::<Value>().checked_add(empty_value_alloc(m)?),
+        Mark::Array(n, m) => empty_items_alloc(*n, mem::size_of::<Value>(), &[m]),
+        Mark::Dict(n, k, v) => empty_items_alloc(*n, mem::size_of::<(Value, Value)>(), &[k, v]),
+        _ => Some(0),
+    }
 }
 
+/// An iterator over the values of a [Parser]
+///
+/// This is created by [`Parser::values()`].
+pub struct Values<R> {
+    parser: Parser<R>,
+    done: bool,
+}
+
+impl<R> Values<R> {
+    /// Turn the iterator back into the parser
+    #[inline]
+    pub fn parser(self) -> Parser<R> {
+        self.parser
+    }
+}
+
+impl<R> Iterator for Values<R>
+where
+    R: Read,
+{
+    type Item = Result<Value>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.parser.next_value_or_end() {
+            Ok(Some(value)) => Some(Ok(value)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(err) => {
+                self.done = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+impl<R> FusedIterator for Values<R> where R: Read {}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -417,7 +806,7 @@ mod test {
         let mut parser = Parser::from(b"l\x00\x30\x00\x00\x20\x10\x00\x05");
         let val = parser.next_value().unwrap();
         assert_eq!(val, Value::Long(0x0030000020100005));
-        assert_eq!(parser.0.is_empty(), true);
+        assert_eq!(parser.reader.is_empty(), true);
     }
 
     #[test]
@@ -425,7 +814,7 @@ mod test {
         let mut parser = Parser::from(b"i\x03\x00\x00\x00");
         let val = parser.next_value().unwrap();
         assert_eq!(val, Value::Int(0x03000000));
-        assert_eq!(parser.0.is_empty(), true);
+        assert_eq!(parser.reader.is_empty(), true);
     }
 
     #[test]
@@ -433,7 +822,7 @@ mod test {
         let mut parser = Parser::from(b"h\x03\x00");
         let val = parser.next_value().unwrap();
         assert_eq!(val, Value::Short(0x0300));
-        assert_eq!(parser.0.is_empty(), true);
+        assert_eq!(parser.reader.is_empty(), true);
     }
 
     #[test]
@@ -441,7 +830,7 @@ mod test {
         let mut parser = Parser::from(b"c\x03");
         let val = parser.next_value().unwrap();
         assert_eq!(val, Value::Char(0x03));
-        assert_eq!(parser.0.is_empty(), true);
+        assert_eq!(parser.reader.is_empty(), true);
     }
 
     #[test]
@@ -449,7 +838,7 @@ mod test {
         let mut parser = Parser::from(b"f\x00\x00\x00\x00");
         let val = parser.next_value().unwrap();
         assert_eq!(val, Value::Float(0.0));
-        assert_eq!(parser.0.is_empty(), true);
+        assert_eq!(parser.reader.is_empty(), true);
     }
 
     #[test]
@@ -457,7 +846,7 @@ mod test {
         let mut parser = Parser::from(b"d\x00\x00\x00\x00\x00\x00\x00\x00");
         let val = parser.next_value().unwrap();
         assert_eq!(val, Value::Double(0.0));
-        assert_eq!(parser.0.is_empty(), true);
+        assert_eq!(parser.reader.is_empty(), true);
     }
 
     #[test]
@@ -465,7 +854,7 @@ mod test {
         let mut parser = Parser::from(b"b\x00\x00\x00\x0bHello World");
         let val = parser.next_value().unwrap();
         assert_eq!(val, Value::Bytes(b"Hello World".to_vec()));
-        assert_eq!(parser.0.is_empty(), true);
+        assert_eq!(parser.reader.is_empty(), true);
     }
 
     #[test]
@@ -473,7 +862,7 @@ mod test {
         let mut parser = Parser::from(b"s\x00\x00\x00\x0bHello World");
         let val = parser.next_value().unwrap();
         assert_eq!(val, Value::Str("Hello World".to_owned()));
-        assert_eq!(parser.0.is_empty(), true);
+        assert_eq!(parser.reader.is_empty(), true);
     }
 
     #[test]
@@ -481,7 +870,7 @@ mod test {
         let mut parser = Parser::from(b"o\x00\x00\x00\x0bHello World");
         let val = parser.next_value().unwrap();
         assert_eq!(val, Value::Object(b"Hello World".to_vec()));
-        assert_eq!(parser.0.is_empty(), true);
+        assert_eq!(parser.reader.is_empty(), true);
     }
 
     #[test]
@@ -489,7 +878,7 @@ mod test {
         let mut parser = Parser::from(b"ei\x00\x00\x00\x01\x00\x00\x00\xfe");
         let val = parser.next_value().unwrap();
         assert_eq!(val, Value::Enum(1, Box::new(Value::Int(0xfe))));
-        assert_eq!(parser.0.is_empty(), true);
+        assert_eq!(parser.reader.is_empty(), true);
     }
 
     #[test]
@@ -497,7 +886,7 @@ mod test {
         let mut parser = Parser::from(b"n");
         let val = parser.next_value().unwrap();
         assert_eq!(val, Value::Null);
-        assert_eq!(parser.0.is_empty(), true);
+        assert_eq!(parser.reader.is_empty(), true);
     }
 
     #[test]
@@ -513,7 +902,7 @@ mod test {
         } else {
             panic!("value is not a list");
         }
-        assert_eq!(parser.0.is_empty(), true);
+        assert_eq!(parser.reader.is_empty(), true);
     }
 
     #[test]
@@ -552,7 +941,7 @@ mod test {
         } else {
             panic!("value is not a list");
         }
-        assert_eq!(parser.0.is_empty(), true);
+        assert_eq!(parser.reader.is_empty(), true);
     }
 
     #[test]
@@ -573,7 +962,7 @@ mod test {
         } else {
             panic!("value is not a map");
         }
-        assert_eq!(parser.0.is_empty(), true);
+        assert_eq!(parser.reader.is_empty(), true);
     }
 
     #[test]
@@ -591,6 +980,152 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_values() {
+        let parser = Parser::from(b"i\x00\x00\x00\x42s\x00\x00\x00\x02hic\x10");
+        let mut values = parser.values();
+
+        assert_eq!(values.next().unwrap().unwrap(), Value::Int(0x42));
+        assert_eq!(values.next().unwrap().unwrap(), Value::Str("hi".to_owned()));
+        assert_eq!(values.next().unwrap().unwrap(), Value::Char(0x10));
+        assert!(values.next().is_none());
+        assert!(values.next().is_none());
+    }
+
+    #[test]
+    fn test_values_empty() {
+        let parser = Parser::from(b"");
+        assert!(parser.values().next().is_none());
+    }
+
+    #[test]
+    fn test_values_eof() {
+        let parser = Parser::from(b"c\x10i\x00\x0a");
+        let mut values = parser.values();
+
+        assert_eq!(values.next().unwrap().unwrap(), Value::Char(0x10));
+
+        let err = values.next().unwrap().expect_err("UnexpectedEof Error");
+        if let Error::IO(e) = err {
+            if e.kind() != std::io::ErrorKind::UnexpectedEof {
+                panic!("Expected UnexpectedEof Error");
+            }
+        } else {
+            panic!("Expected UnexpectedEof Error");
+        }
+        assert!(values.next().is_none());
+    }
+
+    #[test]
+    fn test_peek() {
+        let mut parser = Parser::from(std::io::Cursor::new(
+            b"ac\x00\x00\x00\x02\x01\x02i\x00\x00\x00\x42",
+        ));
+
+        assert_eq!(parser.peek_type().unwrap(), Type::Array);
+        assert_eq!(
+            parser.peek_mark().unwrap(),
+            Mark::Array(2, Box::new(Mark::Char))
+        );
+        assert_eq!(
+            parser.next_value().unwrap(),
+            Value::List(vec![Value::Char(1), Value::Char(2)])
+        );
+
+        assert_eq!(parser.peek_type().unwrap(), Type::Int);
+        assert_eq!(parser.peek_mark().unwrap(), Mark::Int);
+        assert_eq!(parser.next_value().unwrap(), Value::Int(0x42));
+    }
+
+    #[test]
+    fn test_peek_bad_mark() {
+        let mut parser = Parser::from(std::io::Cursor::new(b"ax\x00\x00\x00\x02"));
+
+        parser.peek_mark().expect_err("DataError");
+        assert_eq!(parser.reader.position(), 0);
+    }
+
+    #[test]
+    fn test_huge_str() {
+        let mut parser = Parser::from(b"s\xff\xff\xff\xffHello");
+
+        let err = parser.next_value().expect_err("DataError");
+        if let Error::DataError(_) = err {
+        } else {
+            panic!("Expected a DataError");
+        }
+    }
+
+    #[test]
+    fn test_huge_array() {
+        let mut parser = Parser::from(b"an\xff\xff\xff\xff");
+
+        let err = parser.next_value().expect_err("DataError");
+        if let Error::DataError(_) = err {
+        } else {
+            panic!("Expected a DataError");
+        }
+
+        // Only the data that is actually present gets allocated
+        let mut parser = Parser::from(b"ac\xff\xff\xff\xff\x01\x02");
+        let err = parser.next_value().expect_err("UnexpectedEof Error");
+        if let Error::IO(e) = err {
+            if e.kind() != std::io::ErrorKind::UnexpectedEof {
+                panic!("Expected UnexpectedEof Error");
+            }
+        } else {
+            panic!("Expected UnexpectedEof Error");
+        }
+    }
+
+    #[test]
+    fn test_huge_nested_array() {
+        // 500000 items of 500000 empty items each, without any data
+        let inputs: &[&[u8]] = &[
+            b"aan\x00\x07\xa1\x20\x00\x07\xa1\x20",
+            b"mnan\x00\x07\xa1\x20\x00\x07\xa1\x20",
+            b"aas\x00\x00\x00\x00\x00\x07\xa1\x20\x00\x07\xa1\x20",
+        ];
+        for data in inputs {
+            let mut parser = Parser::from(data);
+            let err = parser.next_value().expect_err("DataError");
+            if let Error::DataError(_) = err {
+            } else {
+                panic!("Expected a DataError");
+            }
+        }
+
+        let mut parser = Parser::from(b"aan\x00\x00\x00\x02\x00\x00\x00\x03");
+        let val = parser.next_value().unwrap();
+        assert_eq!(val, Value::List(vec![Value::List(vec![Value::Null; 2]); 3]));
+    }
+
+    #[test]
+    fn test_max_alloc() {
+        let mut parser = Parser::from(b"b\x00\x00\x00\x05Hello").with_max_alloc(4);
+        parser.next_value().expect_err("DataError");
+
+        let mut parser = Parser::from(b"b\x00\x00\x00\x05Hello").with_max_alloc(5);
+        assert_eq!(
+            parser.next_value().unwrap(),
+            Value::Bytes(b"Hello".to_vec())
+        );
+    }
+
+    #[test]
+    fn test_skip_huge() {
+        let mut parser = Parser::from(b"s\xff\xff\xff\xffHello");
+
+        let err = parser.skip_next().expect_err("UnexpectedEof Error");
+        if let Error::IO(e) = err {
+            if e.kind() != std::io::ErrorKind::UnexpectedEof {
+                panic!("Expected UnexpectedEof Error");
+            }
+        } else {
+            panic!("Expected UnexpectedEof Error");
+        }
+    }
+
     #[test]
     fn test_list_too_big() {
         let mut parser = Parser::from(b"A\x00\x00\x00\x04c\x01i\x00\x00\x00\x00");
@@ -602,6 +1137,17 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_array_overflow() {
+        let mut parser = Parser::from(b"aaal\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff");
+
+        let err = parser.next_value().expect_err("DataError");
+        if let Error::DataError(_) = err {
+        } else {
+            panic!("Expected a DataError");
+        }
+    }
+
     #[test]
     fn test_map_too_big() {
         let mut parser = Parser::from(b"M\x00\x00\x00\x04c\x01i\x00\x00\x00\x00");
@@ -612,4 +1158,90 @@ mod test {
             panic!("Expected a DataError");
         }
     }
+
+    #[test]
+    fn test_error_offset() {
+        let mut parser = Parser::from(b"c\x01A\x00\x00\x00\x04c\x01x\x00");
+        parser.next_value().unwrap();
+
+        let err = parser.next_value().expect_err("DataError");
+        if let Error::DataError(msg) = err {
+            assert!(msg.starts_with("at byte 0x9: "), "{}", msg);
+        } else {
+            panic!("Expected a DataError");
+        }
+    }
+
+    #[test]
+    fn test_error_offset_list() {
+        let mut parser = Parser::from(b"c\x01A\x00\x00\x00\x04c\x01i\x00\x00\x00\x00");
+        parser.next_value().unwrap();
+
+        let err = parser.next_value().expect_err("DataError");
+        if let Error::DataError(msg) = err {
+            assert!(msg.starts_with("at byte 0x2: "), "{}", msg);
+        } else {
+            panic!("Expected a DataError");
+        }
+    }
+
+    fn assert_error_at(data: &[u8], at: &str) {
+        let mut parser = Parser::from(data);
+        parser.next_value().unwrap();
+
+        let err = parser.next_value().expect_err("DataError");
+        if let Error::DataError(msg) = err {
+            assert!(msg.starts_with(at), "{}", msg);
+        } else {
+            panic!("Expected a DataError");
+        }
+    }
+
+    #[test]
+    fn test_error_offset_array() {
+        assert_error_at(
+            b"c\x00as\x00\x00\x00\x01\x00\x00\x00\x02\x41\xff",
+            "at byte 0x2: ",
+        );
+    }
+
+    #[test]
+    fn test_error_offset_dict() {
+        assert_error_at(
+            b"c\x00mcs\x00\x00\x00\x01\x00\x00\x00\x01\x01\xff",
+            "at byte 0x2: ",
+        );
+    }
+
+    #[test]
+    fn test_error_offset_enum() {
+        assert_error_at(
+            b"c\x00es\x00\x00\x00\x01\x00\x00\x00\x00\xff",
+            "at byte 0x2: ",
+        );
+    }
+
+    #[test]
+    fn test_error_offset_overflow() {
+        assert_error_at(
+            b"c\x00aaal\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff",
+            "at byte 0x2: ",
+        );
+    }
+
+    #[test]
+    fn test_offset() {
+        let mut parser = Parser::from(std::io::Cursor::new(
+            b"c\x01s\x00\x00\x00\x02hii\x00\x00\x00\x02",
+        ));
+
+        parser.skip_next().unwrap();
+        assert_eq!(parser.offset(), 2);
+        parser.peek_mark().unwrap();
+        assert_eq!(parser.offset(), 2);
+        parser.seek_next().unwrap();
+        assert_eq!(parser.offset(), 9);
+        parser.next_value().unwrap();
+        assert_eq!(parser.offset(), 14);
+    }
 }